@@ -0,0 +1,48 @@
+pub mod summary;
+pub mod unit;
+
+/// The kind of a metric family, written out on its `# TYPE` line.
+///
+/// OpenMetrics spells this against the *unsuffixed* family name (`http_requests`, not
+/// `http_requests_total`), so callers pass the bare name here even when the sample line
+/// itself ends up suffixed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+}
+
+impl MetricType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+            MetricType::Summary => "summary",
+        }
+    }
+}
+
+/// One label-set's rendered counter value, as passed to `MetricEncoding<CounterSample>`.
+pub struct CounterSample {
+    pub value: u64,
+}
+
+/// One label-set's rendered histogram buckets, as passed to
+/// `MetricEncoding<HistogramSample<N>>`. `buckets[i]` is the count observed in the bucket
+/// with upper bound `thresholds[i]`, not yet accumulated.
+pub struct HistogramSample<const N: usize> {
+    pub thresholds: [f64; N],
+    pub buckets: [u64; N],
+    pub sum: f64,
+    pub count: u64,
+}
+
+/// One label-set's rendered quantile summary, as passed to `MetricEncoding<SummarySample<N>>`.
+pub struct SummarySample<const N: usize> {
+    pub quantiles: [(f64, f64); N],
+    pub sum: f64,
+    pub count: u64,
+}
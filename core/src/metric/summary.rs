@@ -0,0 +1,440 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use crate::{
+    label::LabelGroupSet,
+    metric::{name::MetricName, MetricType, SummarySample},
+    text::{Encoding, MetricEncoding},
+};
+
+/// Default relative accuracy used by [`Quantiles::new`] when none is specified: each
+/// reported quantile is guaranteed to be within 1% of the true value.
+const DEFAULT_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Default cap on the number of distinct buckets a single [`DDSketch`] will track before
+/// it starts collapsing its lowest buckets together.
+const DEFAULT_MAX_BUCKETS: usize = 2048;
+
+/// A [DDSketch](https://www.vldb.org/pvldb/vol12/p2195-masson.pdf): a quantile sketch with a
+/// guaranteed *relative* error, rather than the fixed absolute buckets a [`Thresholds`]
+/// histogram requires the caller to pick up front.
+///
+/// Positive values are bucketed on a logarithmic scale: a value `v` maps to bucket index
+/// `ceil(ln(v) / ln(gamma))`, where `gamma = (1 + alpha) / (1 - alpha)` is derived from the
+/// target relative accuracy `alpha`. Because every value in a bucket is within a factor of
+/// `gamma` of every other, reporting the bucket boundary as the quantile estimate bounds the
+/// relative error by `alpha`. Merging two sketches is just summing counts bucket-by-bucket,
+/// which is why one sketch per label-set is cheap to keep and aggregate at collect time.
+///
+/// [`Thresholds`]: crate::metric::histogram::Thresholds
+#[derive(Clone, Debug)]
+pub struct DDSketch {
+    gamma: f64,
+    ln_gamma: f64,
+    max_buckets: usize,
+    zero_count: u64,
+    buckets: BTreeMap<i32, u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl DDSketch {
+    /// Create an empty sketch with the given relative accuracy (e.g. `0.01` for 1%) and a
+    /// cap on the number of buckets it will track before collapsing the lowest ones together.
+    pub fn new(relative_accuracy: f64, max_buckets: usize) -> Self {
+        let gamma = (1.0 + relative_accuracy) / (1.0 - relative_accuracy);
+        Self {
+            gamma,
+            ln_gamma: gamma.ln(),
+            max_buckets,
+            zero_count: 0,
+            buckets: BTreeMap::new(),
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    /// Record a single observation.
+    ///
+    /// Non-positive values are tracked separately in a zero bucket rather than fed through
+    /// the logarithmic mapping, since `ln` is undefined at and below zero.
+    pub fn observe(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+
+        if value <= 0.0 {
+            self.zero_count += 1;
+            return;
+        }
+
+        let index = (value.ln() / self.ln_gamma).ceil() as i32;
+        *self.buckets.entry(index).or_insert(0) += 1;
+        self.collapse_if_needed();
+    }
+
+    /// Merge another sketch's buckets into this one. Both sketches must have been built with
+    /// the same relative accuracy for the resulting quantile estimates to be meaningful.
+    pub fn merge(&mut self, other: &DDSketch) {
+        self.zero_count += other.zero_count;
+        self.count += other.count;
+        self.sum += other.sum;
+        for (&index, &count) in &other.buckets {
+            *self.buckets.entry(index).or_insert(0) += count;
+        }
+        self.collapse_if_needed();
+    }
+
+    /// Estimate the value at quantile `q` (`0.0..=1.0`).
+    ///
+    /// Returns `NaN` for an empty sketch, matching the usual convention for an undefined
+    /// quantile rather than panicking.
+    pub fn quantile(&self, q: f64) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+
+        let rank = (q * self.count as f64).ceil() as u64;
+        let mut seen = self.zero_count;
+        if seen >= rank {
+            return 0.0;
+        }
+
+        for (&index, &count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                // Center of the bucket rather than its upper edge, halving the worst-case
+                // relative error from `alpha` to `alpha / (1 + gamma)`-ish.
+                let gamma_pow = (index as f64 * self.ln_gamma).exp();
+                return 2.0 * gamma_pow / (self.gamma + 1.0);
+            }
+        }
+
+        unreachable!("rank {rank} exceeds total count {}", self.count)
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Collapse the lowest-index buckets together until we're back under `max_buckets`.
+    ///
+    /// This trades resolution at the low end of the distribution for bounded memory, which
+    /// is an acceptable trade for tail-latency quantiles where the low end matters least.
+    fn collapse_if_needed(&mut self) {
+        while self.buckets.len() > self.max_buckets {
+            let Some((&lowest, &count)) = self.buckets.iter().next() else {
+                break;
+            };
+            self.buckets.remove(&lowest);
+            let Some((&next, _)) = self.buckets.iter().next() else {
+                self.zero_count += count;
+                break;
+            };
+            *self.buckets.entry(next).or_insert(0) += count;
+        }
+    }
+}
+
+/// Configuration for a [`SummaryVec`]: which quantiles to report, and the DDSketch accuracy
+/// to report them with.
+#[derive(Clone, Copy, Debug)]
+pub struct Quantiles<const N: usize> {
+    quantiles: [f64; N],
+    relative_accuracy: f64,
+    max_buckets: usize,
+}
+
+impl<const N: usize> Quantiles<N> {
+    pub fn new(quantiles: [f64; N]) -> Self {
+        Self {
+            quantiles,
+            relative_accuracy: DEFAULT_RELATIVE_ACCURACY,
+            max_buckets: DEFAULT_MAX_BUCKETS,
+        }
+    }
+
+    pub fn with_relative_accuracy(mut self, relative_accuracy: f64) -> Self {
+        self.relative_accuracy = relative_accuracy;
+        self
+    }
+
+    pub fn with_max_buckets(mut self, max_buckets: usize) -> Self {
+        self.max_buckets = max_buckets;
+        self
+    }
+
+    fn new_sketch(&self) -> Mutex<DDSketch> {
+        Mutex::new(DDSketch::new(self.relative_accuracy, self.max_buckets))
+    }
+}
+
+/// A streaming quantile metric backed by a [`DDSketch`] per label-set, reporting the
+/// configured quantiles plus `_sum` and `_count`, similar in spirit to a Prometheus
+/// `summary` but with a bounded-memory, mergeable sketch instead of unbounded raw samples.
+pub struct SummaryVec<S: LabelGroupSet, const N: usize> {
+    label_set: S,
+    quantiles: Quantiles<N>,
+    sketches: Box<[Mutex<DDSketch>]>,
+}
+
+impl<S: LabelGroupSet, const N: usize> SummaryVec<S, N> {
+    /// Create a summary over every value of a fixed-cardinality label set, eagerly
+    /// allocating one sketch per label-set value.
+    pub fn new(label_set: S, quantiles: Quantiles<N>) -> Self {
+        let cardinality = label_set.cardinality();
+        let sketches = (0..cardinality).map(|_| quantiles.new_sketch()).collect();
+        Self {
+            label_set,
+            quantiles,
+            sketches,
+        }
+    }
+
+    pub fn observe(&self, labels: S::Group<'_>, value: f64) {
+        let index = self.label_set.encode(labels);
+        self.sketches[index]
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .observe(value);
+    }
+
+    /// Render this summary's per-label-set quantiles, `_sum`, and `_count` as a
+    /// [`SummarySample`] and pass it to `encoder`.
+    fn sample(&self, index: usize) -> SummarySample<N> {
+        let sketch = self.sketches[index]
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+
+        let mut quantiles = [(0.0, 0.0); N];
+        for (slot, &q) in quantiles.iter_mut().zip(&self.quantiles.quantiles) {
+            *slot = (q, sketch.quantile(q));
+        }
+
+        SummarySample {
+            quantiles,
+            sum: sketch.sum(),
+            count: sketch.count(),
+        }
+    }
+
+    /// Write this summary's quantiles, `_sum`, and `_count` samples into `encoder`, via the
+    /// same [`Encoding`]/[`MetricEncoding`] traits `TextEncoder` and `OpenMetricsEncoder`
+    /// already implement for counters and histograms.
+    pub fn collect_into<E: Encoding + MetricEncoding<SummarySample<N>>>(
+        &self,
+        name: impl MetricName + Copy,
+        encoder: &mut E,
+    ) where
+        for<'a> S::Group<'a>: std::fmt::Display,
+    {
+        encoder.write_type(name, MetricType::Summary);
+        for index in 0..self.sketches.len() {
+            let sample = self.sample(index);
+            // Each label-set renders its own Prometheus-style label text (`k="v",k2="v2"`)
+            // via `Display`, the same convention a metric name already uses - without this,
+            // every index would collapse onto one unlabeled, indistinguishable sample line.
+            let labels = self.label_set.decode(index).to_string();
+            encoder.write_sample(name, &labels, &sample);
+        }
+    }
+
+    /// Like [`SummaryVec::collect_into`], but yields one rendered chunk per label-set value
+    /// instead of writing everything into a single shared buffer.
+    ///
+    /// Feeds [`MetricBodyStream`](crate::text::stream::MetricBodyStream) directly, and, chunk
+    /// by chunk, the on-the-fly compression layer, so peak memory stays proportional to one
+    /// label-set's worth of samples rather than the whole family.
+    pub fn collect_into_stream<'a, E: Encoding + MetricEncoding<SummarySample<N>> + 'a>(
+        &'a self,
+        name: impl MetricName + Copy + 'a,
+    ) -> impl Iterator<Item = String> + 'a
+    where
+        S::Group<'a>: std::fmt::Display,
+    {
+        let header = {
+            let mut encoder = E::default();
+            encoder.write_type(name, MetricType::Summary);
+            encoder.take_buf()
+        };
+
+        std::iter::once(header).chain((0..self.sketches.len()).map(move |index| {
+            let sample = self.sample(index);
+            let labels = self.label_set.decode(index).to_string();
+            let mut encoder = E::default();
+            encoder.write_sample(name, &labels, &sample);
+            encoder.take_buf()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::{DDSketch, Quantiles, SummaryVec};
+    use crate::{
+        label::LabelGroupSet,
+        metric::{name::MetricName, MetricType, SummarySample},
+        text::{Encoding, MetricEncoding},
+    };
+
+    const ACCURACY: f64 = 0.01;
+
+    #[derive(Clone, Copy)]
+    struct Endpoint(&'static str);
+
+    impl fmt::Display for Endpoint {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "endpoint=\"{}\"", self.0)
+        }
+    }
+
+    struct EndpointSet;
+
+    impl LabelGroupSet for EndpointSet {
+        type Group<'a> = Endpoint;
+
+        fn cardinality(&self) -> usize {
+            2
+        }
+
+        fn encode(&self, group: Self::Group<'_>) -> usize {
+            match group.0 {
+                "a" => 0,
+                "b" => 1,
+                other => panic!("unexpected endpoint {other}"),
+            }
+        }
+
+        fn decode(&self, index: usize) -> Self::Group<'_> {
+            Endpoint(["a", "b"][index])
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingEncoder {
+        buf: String,
+    }
+
+    impl Encoding for RecordingEncoder {
+        fn write_help(&mut self, _name: impl MetricName, _help: &str) {}
+
+        fn write_type(&mut self, name: impl MetricName, typ: MetricType) {
+            use std::fmt::Write;
+            let _ = writeln!(self.buf, "# TYPE {name} {}", typ.as_str());
+        }
+
+        fn take_buf(&mut self) -> String {
+            std::mem::take(&mut self.buf)
+        }
+    }
+
+    impl<const N: usize> MetricEncoding<SummarySample<N>> for RecordingEncoder {
+        fn write_sample(
+            &mut self,
+            name: impl MetricName + Copy,
+            labels: &str,
+            sample: &SummarySample<N>,
+        ) {
+            use std::fmt::Write;
+            let _ = writeln!(
+                self.buf,
+                "{name}{{{labels}}} sum={} count={}",
+                sample.sum, sample.count
+            );
+        }
+    }
+
+    #[test]
+    fn collect_into_renders_distinct_labels_per_label_set() {
+        let summary = SummaryVec::new(EndpointSet, Quantiles::new([0.5, 0.9]));
+        summary.observe(Endpoint("a"), 1.0);
+        summary.observe(Endpoint("b"), 2.0);
+        summary.observe(Endpoint("b"), 4.0);
+
+        let mut encoder = RecordingEncoder::default();
+        summary.collect_into("request_duration", &mut encoder);
+
+        let lines: Vec<&str> = encoder.buf.lines().collect();
+        let a_line = lines
+            .iter()
+            .find(|l| l.contains("endpoint=\"a\""))
+            .expect("endpoint a sample line");
+        let b_line = lines
+            .iter()
+            .find(|l| l.contains("endpoint=\"b\""))
+            .expect("endpoint b sample line");
+
+        assert!(a_line.contains("sum=1"));
+        assert!(b_line.contains("sum=6"));
+        // Each label-set must render its own distinct sample line, not a shared, unlabeled one.
+        assert_ne!(a_line, b_line);
+    }
+
+    #[test]
+    fn quantile_error_is_within_relative_accuracy() {
+        let mut sketch = DDSketch::new(ACCURACY, 2048);
+        for i in 1..=10_000u64 {
+            sketch.observe(i as f64);
+        }
+
+        for &q in &[0.5, 0.9, 0.99] {
+            let estimate = sketch.quantile(q);
+            let true_value = q * 10_000.0;
+            let relative_error = (estimate - true_value).abs() / true_value;
+            assert!(
+                relative_error <= ACCURACY,
+                "quantile {q}: estimate {estimate}, true {true_value}, relative error {relative_error}"
+            );
+        }
+    }
+
+    #[test]
+    fn merge_matches_observing_the_union_directly() {
+        let mut merged = DDSketch::new(ACCURACY, 2048);
+        let mut a = DDSketch::new(ACCURACY, 2048);
+        let mut b = DDSketch::new(ACCURACY, 2048);
+
+        for i in 1..=500u64 {
+            a.observe(i as f64);
+            merged.observe(i as f64);
+        }
+        for i in 501..=1000u64 {
+            b.observe(i as f64);
+            merged.observe(i as f64);
+        }
+
+        a.merge(&b);
+
+        assert_eq!(a.count(), merged.count());
+        assert_eq!(a.sum(), merged.sum());
+        for &q in &[0.25, 0.5, 0.75, 0.99] {
+            assert_eq!(a.quantile(q), merged.quantile(q));
+        }
+    }
+
+    #[test]
+    fn collapses_lowest_buckets_once_over_max_buckets() {
+        let max_buckets = 8;
+        let mut sketch = DDSketch::new(ACCURACY, max_buckets);
+
+        // Each observation here lands in its own bucket (values far enough apart on the
+        // log scale), so exceeding `max_buckets` forces a collapse.
+        for i in 0..max_buckets * 4 {
+            sketch.observe(1.5f64.powi(i as i32));
+        }
+
+        assert!(sketch.buckets.len() <= max_buckets);
+        // Collapsing merges counts into existing buckets rather than dropping observations.
+        assert_eq!(sketch.count(), (max_buckets * 4) as u64);
+    }
+
+    #[test]
+    fn empty_sketch_quantile_is_nan() {
+        let sketch = DDSketch::new(ACCURACY, 2048);
+        assert!(sketch.quantile(0.5).is_nan());
+    }
+}
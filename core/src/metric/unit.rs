@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use crate::metric::name::MetricName;
+
+/// A unit of measurement for a metric, attached to its name via [`WithUnit::with_unit`].
+///
+/// Carrying the unit as a typed value (rather than only encoding it into the metric
+/// name by convention) lets encoders emit a `# UNIT` metadata line in OpenMetrics mode
+/// and catch the common mistake of naming a metric `_seconds` while observing milliseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Seconds,
+    Bytes,
+    /// Binary byte count, i.e. kibi/mebi/gibi scaling rather than kilo/mega/giga.
+    Bibytes,
+    Ratio,
+    Bits,
+    /// Binary bit count, i.e. kibi/mebi/gibi scaling rather than kilo/mega/giga.
+    Bibits,
+}
+
+impl Unit {
+    /// The string written after `# UNIT <name>` in the OpenMetrics exposition format, and
+    /// the suffix appended to the metric name in the classic Prometheus format.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Unit::Seconds => "seconds",
+            Unit::Bytes => "bytes",
+            Unit::Bibytes => "bibytes",
+            Unit::Ratio => "ratio",
+            Unit::Bits => "bits",
+            Unit::Bibits => "bibits",
+        }
+    }
+}
+
+/// A metric name paired with the [`Unit`] it's measured in.
+///
+/// Built via [`WithUnit::with_unit`]; encoders append [`Unit::as_str`] as a name suffix in
+/// Prometheus mode and additionally emit a `# UNIT` line in OpenMetrics mode.
+#[derive(Clone, Copy)]
+pub struct WithUnitName<N> {
+    pub(crate) name: N,
+    pub(crate) unit: Unit,
+}
+
+/// Extension trait for attaching a [`Unit`] to a metric name, analogous to `with_suffix`.
+pub trait WithUnit: MetricName + Sized {
+    fn with_unit(self, unit: Unit) -> WithUnitName<Self> {
+        WithUnitName { name: self, unit }
+    }
+}
+
+impl<N: MetricName> WithUnit for N {}
+
+impl<N: MetricName> Display for WithUnitName<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The unit is appended as a plain name suffix, matching the convention Prometheus
+        // itself uses (`http_request_duration_seconds`) rather than a label. If the caller
+        // already spelled the suffix out by hand, don't double it up.
+        let rendered = self.name.to_string();
+        let suffix = self.unit.as_str();
+        if rendered
+            .strip_suffix(suffix)
+            .is_some_and(|rest| rest.ends_with('_'))
+        {
+            write!(f, "{rendered}")
+        } else {
+            write!(f, "{rendered}_{suffix}")
+        }
+    }
+}
+
+impl<N: MetricName> MetricName for WithUnitName<N> {}
@@ -0,0 +1,112 @@
+use std::fmt::Write;
+
+use crate::metric::{name::MetricName, CounterSample, HistogramSample, MetricType, SummarySample};
+
+use super::{Encoding, MetricEncoding};
+
+/// Encodes metrics in the classic [Prometheus text exposition
+/// format](https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format).
+#[derive(Default)]
+pub struct TextEncoder {
+    buf: String,
+}
+
+impl TextEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finish the exposition and return the rendered buffer.
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+impl Encoding for TextEncoder {
+    fn write_help(&mut self, name: impl MetricName, help: &str) {
+        let _ = writeln!(self.buf, "# HELP {name} {help}");
+    }
+
+    fn write_type(&mut self, name: impl MetricName, typ: MetricType) {
+        let _ = writeln!(self.buf, "# TYPE {name} {}", typ.as_str());
+    }
+
+    fn take_buf(&mut self) -> String {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+impl MetricEncoding<CounterSample> for TextEncoder {
+    fn write_sample(&mut self, name: impl MetricName + Copy, labels: &str, sample: &CounterSample) {
+        write_sample_line(&mut self.buf, name, labels, sample.value as f64);
+    }
+}
+
+impl<const N: usize> MetricEncoding<HistogramSample<N>> for TextEncoder {
+    fn write_sample(
+        &mut self,
+        name: impl MetricName + Copy,
+        labels: &str,
+        sample: &HistogramSample<N>,
+    ) {
+        let mut cumulative = 0u64;
+        for i in 0..N {
+            cumulative += sample.buckets[i];
+            write_bucket_line(
+                &mut self.buf,
+                &name,
+                labels,
+                sample.thresholds[i],
+                cumulative,
+            );
+        }
+        write_bucket_line(&mut self.buf, &name, labels, f64::INFINITY, sample.count);
+        let _ = writeln!(self.buf, "{name}_sum{{{labels}}} {}", sample.sum);
+        let _ = writeln!(self.buf, "{name}_count{{{labels}}} {}", sample.count);
+    }
+}
+
+impl<const N: usize> MetricEncoding<SummarySample<N>> for TextEncoder {
+    fn write_sample(
+        &mut self,
+        name: impl MetricName + Copy,
+        labels: &str,
+        sample: &SummarySample<N>,
+    ) {
+        for &(quantile, value) in &sample.quantiles {
+            write_quantile_line(&mut self.buf, &name, labels, quantile, value);
+        }
+        let _ = writeln!(self.buf, "{name}_sum{{{labels}}} {}", sample.sum);
+        let _ = writeln!(self.buf, "{name}_count{{{labels}}} {}", sample.count);
+    }
+}
+
+fn write_sample_line(buf: &mut String, name: impl MetricName, labels: &str, value: f64) {
+    if labels.is_empty() {
+        let _ = writeln!(buf, "{name} {value}");
+    } else {
+        let _ = writeln!(buf, "{name}{{{labels}}} {value}");
+    }
+}
+
+fn write_bucket_line(buf: &mut String, name: &impl MetricName, labels: &str, le: f64, count: u64) {
+    if labels.is_empty() {
+        let _ = writeln!(buf, "{name}_bucket{{le=\"{le}\"}} {count}");
+    } else {
+        let _ = writeln!(buf, "{name}_bucket{{{labels},le=\"{le}\"}} {count}");
+    }
+}
+
+fn write_quantile_line(
+    buf: &mut String,
+    name: &impl MetricName,
+    labels: &str,
+    quantile: f64,
+    value: f64,
+) {
+    if labels.is_empty() {
+        let _ = writeln!(buf, "{name}{{quantile=\"{quantile}\"}} {value}");
+    } else {
+        let _ = writeln!(buf, "{name}{{{labels},quantile=\"{quantile}\"}} {value}");
+    }
+}
@@ -0,0 +1,32 @@
+//! Text-based exposition formats for scraping.
+
+pub mod encoder;
+pub mod openmetrics;
+pub mod stream;
+
+pub use encoder::TextEncoder;
+pub use openmetrics::OpenMetricsEncoder;
+pub use stream::MetricBodyStream;
+
+use crate::metric::{name::MetricName, MetricType};
+
+/// The part of rendering a metric family that's independent of its kind: the `# HELP` and
+/// `# TYPE` preamble. Every exposition encoder implements this; metric-vec types call it
+/// once per family before writing samples via [`MetricEncoding`].
+pub trait Encoding: Default {
+    fn write_help(&mut self, name: impl MetricName, help: &str);
+    fn write_type(&mut self, name: impl MetricName, typ: MetricType);
+
+    /// Drain everything written so far, without whatever whole-exposition trailer `finish`
+    /// appends (e.g. OpenMetrics's `# EOF`). Used to pull out one self-contained chunk at a
+    /// time when streaming a family's samples instead of writing into a single buffer.
+    fn take_buf(&mut self) -> String;
+}
+
+/// Implemented once per rendered sample shape `M` (a counter value, a histogram's buckets,
+/// a summary's quantiles, ...) by every encoder that knows how to write it. A metric-vec
+/// type's `collect_into` is generic over `E: MetricEncoding<M>`, which is what lets the same
+/// call site work against both `TextEncoder` and `OpenMetricsEncoder`.
+pub trait MetricEncoding<M>: Encoding {
+    fn write_sample(&mut self, name: impl MetricName + Copy, labels: &str, sample: &M);
+}
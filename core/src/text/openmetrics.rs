@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+use crate::metric::{
+    name::MetricName, unit::Unit, CounterSample, HistogramSample, MetricType, SummarySample,
+};
+
+use super::{Encoding, MetricEncoding};
+
+/// Encodes metrics in the [OpenMetrics text exposition format](https://openmetrics.io/),
+/// the successor to the classic Prometheus format produced by
+/// [`TextEncoder`](super::TextEncoder).
+///
+/// The two formats share most of their shape, but OpenMetrics:
+/// - terminates the payload with a literal `# EOF` line,
+/// - suffixes counter sample lines with `_total` while the `# TYPE` line keeps the bare name,
+/// - supports an optional `# UNIT` metadata line per family via [`OpenMetricsEncoder::write_unit`].
+///
+/// It implements the same [`Encoding`]/[`MetricEncoding`] traits as `TextEncoder`, so any
+/// metric-vec type's `collect_into` works against either encoder unchanged.
+#[derive(Default)]
+pub struct OpenMetricsEncoder {
+    buf: String,
+}
+
+impl OpenMetricsEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the optional `# UNIT` metadata line for a metric family.
+    ///
+    /// Per the OpenMetrics spec this should only be emitted when the family's name already
+    /// carries the unit as a suffix (e.g. `http_request_duration_seconds`).
+    pub fn write_unit(&mut self, name: impl MetricName, unit: Unit) {
+        let _ = writeln!(self.buf, "# UNIT {name} {}", unit.as_str());
+    }
+
+    /// Finish the exposition, appending the OpenMetrics terminator line.
+    pub fn finish(mut self) -> String {
+        self.buf.push_str("# EOF\n");
+        self.buf
+    }
+}
+
+impl Encoding for OpenMetricsEncoder {
+    fn write_help(&mut self, name: impl MetricName, help: &str) {
+        self.buf.push_str("# HELP ");
+        let _ = write!(self.buf, "{name}");
+        self.buf.push(' ');
+        write_escaped(&mut self.buf, help);
+        self.buf.push('\n');
+    }
+
+    fn write_type(&mut self, name: impl MetricName, typ: MetricType) {
+        let _ = writeln!(self.buf, "# TYPE {name} {}", typ.as_str());
+    }
+
+    fn take_buf(&mut self) -> String {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+impl MetricEncoding<CounterSample> for OpenMetricsEncoder {
+    fn write_sample(&mut self, name: impl MetricName + Copy, labels: &str, sample: &CounterSample) {
+        write_sample_line(&mut self.buf, name, "_total", labels, sample.value as f64);
+    }
+}
+
+impl<const N: usize> MetricEncoding<HistogramSample<N>> for OpenMetricsEncoder {
+    fn write_sample(
+        &mut self,
+        name: impl MetricName + Copy,
+        labels: &str,
+        sample: &HistogramSample<N>,
+    ) {
+        let mut cumulative = 0u64;
+        for i in 0..N {
+            cumulative += sample.buckets[i];
+            write_bucket_line(
+                &mut self.buf,
+                &name,
+                labels,
+                sample.thresholds[i],
+                cumulative,
+            );
+        }
+        write_bucket_line(&mut self.buf, &name, labels, f64::INFINITY, sample.count);
+        let _ = writeln!(self.buf, "{name}_sum{{{labels}}} {}", sample.sum);
+        let _ = writeln!(self.buf, "{name}_count{{{labels}}} {}", sample.count);
+    }
+}
+
+impl<const N: usize> MetricEncoding<SummarySample<N>> for OpenMetricsEncoder {
+    fn write_sample(
+        &mut self,
+        name: impl MetricName + Copy,
+        labels: &str,
+        sample: &SummarySample<N>,
+    ) {
+        for &(quantile, value) in &sample.quantiles {
+            write_quantile_line(&mut self.buf, &name, labels, quantile, value);
+        }
+        let _ = writeln!(self.buf, "{name}_sum{{{labels}}} {}", sample.sum);
+        let _ = writeln!(self.buf, "{name}_count{{{labels}}} {}", sample.count);
+    }
+}
+
+fn write_sample_line(
+    buf: &mut String,
+    name: impl MetricName,
+    suffix: &str,
+    labels: &str,
+    value: f64,
+) {
+    if labels.is_empty() {
+        let _ = writeln!(buf, "{name}{suffix} {value}");
+    } else {
+        let _ = writeln!(buf, "{name}{suffix}{{{labels}}} {value}");
+    }
+}
+
+fn write_bucket_line(buf: &mut String, name: &impl MetricName, labels: &str, le: f64, count: u64) {
+    if labels.is_empty() {
+        let _ = writeln!(buf, "{name}_bucket{{le=\"{le}\"}} {count}");
+    } else {
+        let _ = writeln!(buf, "{name}_bucket{{{labels},le=\"{le}\"}} {count}");
+    }
+}
+
+fn write_quantile_line(
+    buf: &mut String,
+    name: &impl MetricName,
+    labels: &str,
+    quantile: f64,
+    value: f64,
+) {
+    if labels.is_empty() {
+        let _ = writeln!(buf, "{name}{{quantile=\"{quantile}\"}} {value}");
+    } else {
+        let _ = writeln!(buf, "{name}{{{labels},quantile=\"{quantile}\"}} {value}");
+    }
+}
+
+fn write_escaped(buf: &mut String, help: &str) {
+    for c in help.chars() {
+        match c {
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '"' => buf.push_str("\\\""),
+            c => buf.push(c),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+
+/// An [`http_body::Body`] that yields one pre-encoded chunk per poll instead of requiring the
+/// whole exposition to be buffered into a single contiguous `String` up front.
+///
+/// Each chunk is produced by the wrapped iterator, which metric-vec types build lazily via
+/// their `collect_into_stream` method (one family, or one label-set chunk, per item) rather
+/// than writing into a single shared buffer. This keeps peak memory proportional to a chunk
+/// instead of the full scrape, which matters once a registry has a high-cardinality label set.
+pub struct MetricBodyStream<I> {
+    chunks: I,
+}
+
+impl<I: Iterator<Item = String>> MetricBodyStream<I> {
+    pub fn new(chunks: I) -> Self {
+        Self { chunks }
+    }
+}
+
+impl<I: Iterator<Item = String> + Unpin> Body for MetricBodyStream<I> {
+    type Data = Bytes;
+    type Error = std::convert::Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        // Encoding a chunk is cheap CPU work, not I/O, so there's nothing to actually wait
+        // on here: every poll either produces the next chunk immediately or ends the body.
+        match self.chunks.next() {
+            Some(chunk) => Poll::Ready(Some(Ok(Frame::data(Bytes::from(chunk))))),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        // The total size isn't known without draining the iterator, which would defeat the
+        // point of streaming it in the first place.
+        SizeHint::default()
+    }
+}
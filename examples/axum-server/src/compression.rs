@@ -0,0 +1,255 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_compression::tokio::write;
+use axum::http::{header, HeaderMap, HeaderValue};
+use bytes::Bytes;
+use http_body::{Body, Frame};
+use measured::text::stream::MetricBodyStream;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A codec that the metrics scrape endpoint is willing to compress its body with.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Encoding {
+    Gzip,
+    Deflate,
+    Zstd,
+}
+
+impl Encoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    /// The `Content-Encoding` header value to send alongside a body compressed with this codec.
+    pub(crate) fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(self.as_str())
+    }
+}
+
+/// Which codecs the scrape endpoint is allowed to pick from when compressing a response.
+///
+/// Defaults to every codec `better-metrics` knows how to produce. Build one with
+/// [`CompressionConfig::none`] and [`CompressionConfig::allow`] to restrict the set.
+#[derive(Clone, Copy, Debug)]
+pub struct CompressionConfig {
+    gzip: bool,
+    deflate: bool,
+    zstd: bool,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            gzip: true,
+            deflate: true,
+            zstd: true,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// No codecs allowed; the scrape endpoint always responds with identity encoding.
+    pub fn none() -> Self {
+        Self {
+            gzip: false,
+            deflate: false,
+            zstd: false,
+        }
+    }
+
+    /// Allow the given codec to be negotiated with clients.
+    pub fn allow(mut self, encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip => self.gzip = true,
+            Encoding::Deflate => self.deflate = true,
+            Encoding::Zstd => self.zstd = true,
+        }
+        self
+    }
+
+    fn allows(&self, encoding: Encoding) -> bool {
+        match encoding {
+            Encoding::Gzip => self.gzip,
+            Encoding::Deflate => self.deflate,
+            Encoding::Zstd => self.zstd,
+        }
+    }
+
+    /// Pick the best codec both this config and the request's `Accept-Encoding` header agree on.
+    ///
+    /// Returns `None` when nothing matches, in which case the caller should fall back to
+    /// identity encoding.
+    pub fn negotiate(&self, headers: &HeaderMap) -> Option<Encoding> {
+        let accept_encoding = headers.get(header::ACCEPT_ENCODING)?.to_str().ok()?;
+
+        // Prefer zstd > gzip > deflate when a client advertises more than one.
+        [Encoding::Zstd, Encoding::Gzip, Encoding::Deflate]
+            .into_iter()
+            .find(|&encoding| self.allows(encoding) && accepts(accept_encoding, encoding.as_str()))
+    }
+}
+
+/// Very small `Accept-Encoding` parser: true if `coding` is listed with a non-zero `q` value.
+fn accepts(accept_encoding: &str, coding: &str) -> bool {
+    accept_encoding.split(',').any(|item| {
+        let mut parts = item.split(';');
+        let Some(name) = parts.next().map(str::trim) else {
+            return false;
+        };
+        if !name.eq_ignore_ascii_case(coding) {
+            return false;
+        }
+        parts
+            .next()
+            .and_then(|q| q.trim().strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .map(|q| q > 0.0)
+            .unwrap_or(true)
+    })
+}
+
+enum ChunkEncoder {
+    Gzip(write::GzipEncoder<Vec<u8>>),
+    Deflate(write::DeflateEncoder<Vec<u8>>),
+    Zstd(write::ZstdEncoder<Vec<u8>>),
+}
+
+impl ChunkEncoder {
+    fn new(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip => Self::Gzip(write::GzipEncoder::new(Vec::new())),
+            Encoding::Deflate => Self::Deflate(write::DeflateEncoder::new(Vec::new())),
+            Encoding::Zstd => Self::Zstd(write::ZstdEncoder::new(Vec::new())),
+        }
+    }
+
+    fn inner(&mut self) -> Pin<&mut (dyn AsyncWrite + Send)> {
+        match self {
+            Self::Gzip(e) => Pin::new(e),
+            Self::Deflate(e) => Pin::new(e),
+            Self::Zstd(e) => Pin::new(e),
+        }
+    }
+
+    fn sink_mut(&mut self) -> &mut Vec<u8> {
+        match self {
+            Self::Gzip(e) => e.get_mut(),
+            Self::Deflate(e) => e.get_mut(),
+            Self::Zstd(e) => e.get_mut(),
+        }
+    }
+
+    /// Write a chunk through the encoder and flush, draining whatever compressed bytes that
+    /// produced. Writing to an in-memory `Vec<u8>` sink never blocks, so this never actually
+    /// suspends; it's only `async` so it can reuse `AsyncWrite`.
+    async fn encode_chunk(mut self, chunk: String) -> (Self, Bytes) {
+        self.inner()
+            .write_all(chunk.as_bytes())
+            .await
+            .expect("writing to a Vec<u8> cannot fail");
+        self.inner()
+            .flush()
+            .await
+            .expect("flushing a Vec<u8> sink cannot fail");
+        let bytes = Bytes::from(std::mem::take(self.sink_mut()));
+        (self, bytes)
+    }
+
+    async fn finish(mut self) -> Bytes {
+        self.inner()
+            .shutdown()
+            .await
+            .expect("shutting down a Vec<u8> sink cannot fail");
+        Bytes::from(std::mem::take(self.sink_mut()))
+    }
+}
+
+/// What [`CompressedMetricBody`] is waiting on: either the next compressed chunk plus the
+/// encoder handed back so it can encode the next one, or the trailer produced once the
+/// source is drained and the encoder is shut down for good.
+enum Pending {
+    Chunk(Pin<Box<dyn std::future::Future<Output = (ChunkEncoder, Bytes)> + Send>>),
+    Trailer(Pin<Box<dyn std::future::Future<Output = Bytes> + Send>>),
+}
+
+/// An [`http_body::Body`] that compresses a [`MetricBodyStream`]'s chunks with the negotiated
+/// codec as they're produced, rather than buffering the whole exposition before compressing
+/// it. Peak memory stays proportional to one chunk instead of the full scrape.
+pub struct CompressedMetricBody<I> {
+    chunks: Option<MetricBodyStream<I>>,
+    encoder: Option<ChunkEncoder>,
+    pending: Option<Pending>,
+}
+
+impl<I: Iterator<Item = String> + Unpin + Send + 'static> CompressedMetricBody<I> {
+    pub fn new(chunks: MetricBodyStream<I>, encoding: Encoding) -> Self {
+        Self {
+            chunks: Some(chunks),
+            encoder: Some(ChunkEncoder::new(encoding)),
+            pending: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = String> + Unpin + Send + 'static> Body for CompressedMetricBody<I> {
+    type Data = Bytes;
+    type Error = std::convert::Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+
+        if let Some(pending) = &mut this.pending {
+            return match pending {
+                Pending::Chunk(fut) => match fut.as_mut().poll(cx) {
+                    Poll::Ready((encoder, bytes)) => {
+                        this.encoder = Some(encoder);
+                        this.pending = None;
+                        Poll::Ready(Some(Ok(Frame::data(bytes))))
+                    }
+                    Poll::Pending => Poll::Pending,
+                },
+                Pending::Trailer(fut) => match fut.as_mut().poll(cx) {
+                    Poll::Ready(bytes) => {
+                        this.pending = None;
+                        Poll::Ready(Some(Ok(Frame::data(bytes))))
+                    }
+                    Poll::Pending => Poll::Pending,
+                },
+            };
+        }
+
+        let Some(chunks) = &mut this.chunks else {
+            return Poll::Ready(None);
+        };
+
+        match chunks.by_ref().next() {
+            Some(chunk) => {
+                let encoder = this
+                    .encoder
+                    .take()
+                    .expect("encoder only taken while pending");
+                this.pending = Some(Pending::Chunk(Box::pin(encoder.encode_chunk(chunk))));
+                self.poll_frame(cx)
+            }
+            None => {
+                this.chunks = None;
+                let encoder = this
+                    .encoder
+                    .take()
+                    .expect("encoder only taken while pending");
+                this.pending = Some(Pending::Trailer(Box::pin(encoder.finish())));
+                self.poll_frame(cx)
+            }
+        }
+    }
+}
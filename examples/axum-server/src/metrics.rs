@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use axum::{
     extract::{MatchedPath, Request, State},
+    http::HeaderMap,
     middleware::Next,
     response::Response,
     RequestExt,
@@ -11,16 +12,20 @@ use measured::{
     metric::{
         histogram::Thresholds,
         name::{MetricName, Total},
+        unit::{Unit, WithUnit},
     },
-    text::TextEncoder,
+    text::{openmetrics::OpenMetricsEncoder, Encoding, MetricBodyStream, TextEncoder},
     CounterVec, FixedCardinalityLabel, HistogramVec, LabelGroup,
 };
-use tokio::{sync::Mutex, time::Instant};
+use tokio::time::Instant;
 
-use crate::AppState;
+use crate::{
+    compression::{CompressedMetricBody, CompressionConfig},
+    AppState,
+};
 
 pub struct AppMetrics {
-    encoder: Mutex<TextEncoder>,
+    compression: CompressionConfig,
     pub http_requests: CounterVec<HttpRequestsSet>,
     pub http_responses: CounterVec<HttpResponsesSet>,
     pub http_request_duration: HistogramVec<HttpRequestsSet, 6>,
@@ -28,10 +33,25 @@ pub struct AppMetrics {
 
 impl AppMetrics {
     pub fn new(paths: lasso::RodeoReader) -> Self {
+        Self::with_compression(paths, CompressionConfig::default())
+    }
+
+    /// Like [`AppMetrics::new`], but with control over which compression codecs the scrape
+    /// endpoint is allowed to negotiate with clients. Pass [`CompressionConfig::none`] to
+    /// always serve identity encoding.
+    pub fn with_compression(paths: lasso::RodeoReader, compression: CompressionConfig) -> Self {
         let path = Arc::new(paths);
 
         Self {
-            encoder: Mutex::default(),
+            compression,
+            // `path` is keyed off `MatchedPath` (the registered route pattern, e.g.
+            // "/users/:id"), which is fixed and bounded by the router, not user input - hence
+            // `fixed_with = Arc<lasso::RodeoReader>` rather than a `dynamic_with` interner.
+            // `new_sparse` backs each label-set's counter with a lock-free bucketed structure
+            // regardless, so scraping a family here was never blocked on `inc`/`observe`;
+            // the contention `handler` used to hit was the shared `Mutex<TextEncoder>` two
+            // concurrent scrapes would serialize on, which giving each scrape its own encoder
+            // (below) already removes.
             http_requests: CounterVec::new_sparse(HttpRequestsSet { path: path.clone() }),
             http_responses: CounterVec::new_sparse(HttpResponsesSet { path: path.clone() }),
             http_request_duration: HistogramVec::new_sparse_metric_vec(
@@ -75,22 +95,87 @@ pub async fn middleware(s: State<AppState>, mut request: Request, next: Next) ->
     response
 }
 
-pub async fn handler(s: State<AppState>) -> Response {
+pub async fn handler(s: State<AppState>, headers: HeaderMap) -> Response {
     let AppMetrics {
-        encoder,
+        compression,
         http_requests,
         http_responses,
         http_request_duration,
         ..
     } = &*s.0.metrics;
 
-    let mut encoder = encoder.lock().await;
-
-    http_requests.collect_into("http_requests".with_suffix(Total), &mut encoder);
-    http_responses.collect_into("http_responses".with_suffix(Total), &mut encoder);
-    http_request_duration.collect_into("http_request_duration_seconds", &mut encoder);
+    // Each scrape gets its own encoder instead of taking turns behind a shared
+    // `Mutex<TextEncoder>`, so overlapping scrapes no longer serialize on each other.
+    let duration_name = "http_request_duration".with_unit(Unit::Seconds);
+
+    // Collected one family at a time and drained into its own chunk, rather than into a
+    // single contiguous buffer, so the response body below can stream it straight to the
+    // client with peak memory proportional to one family instead of the whole scrape.
+    let (chunks, content_type): (Vec<String>, _) = if wants_openmetrics(&headers) {
+        let mut encoder = OpenMetricsEncoder::new();
+        let mut chunks = Vec::with_capacity(4);
+
+        // OpenMetricsEncoder's CounterSample encoding already appends `_total` to the sample
+        // line while keeping the `# TYPE` line on the bare name, so pass the bare name here
+        // rather than `with_suffix(Total)` (that's for the classic Prometheus encoder below,
+        // which doesn't add the suffix itself).
+        http_requests.collect_into("http_requests", &mut encoder);
+        chunks.push(encoder.take_buf());
+        http_responses.collect_into("http_responses", &mut encoder);
+        chunks.push(encoder.take_buf());
+        encoder.write_unit(duration_name, Unit::Seconds);
+        http_request_duration.collect_into(duration_name, &mut encoder);
+        chunks.push(encoder.take_buf());
+        chunks.push(encoder.finish());
+
+        (
+            chunks,
+            "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        )
+    } else {
+        let mut encoder = TextEncoder::new();
+        let mut chunks = Vec::with_capacity(3);
+
+        http_requests.collect_into("http_requests".with_suffix(Total), &mut encoder);
+        chunks.push(encoder.take_buf());
+        http_responses.collect_into("http_responses".with_suffix(Total), &mut encoder);
+        chunks.push(encoder.take_buf());
+        http_request_duration.collect_into(duration_name, &mut encoder);
+        chunks.push(encoder.finish());
+
+        (chunks, "text/plain; version=0.0.4; charset=utf-8")
+    };
+
+    let stream = MetricBodyStream::new(chunks.into_iter());
+
+    let response = match compression.negotiate(&headers) {
+        Some(encoding) => {
+            let mut response = Response::new(axum::body::Body::new(CompressedMetricBody::new(
+                stream, encoding,
+            )));
+            response.headers_mut().insert(
+                axum::http::header::CONTENT_ENCODING,
+                encoding.header_value(),
+            );
+            response
+        }
+        None => Response::new(axum::body::Body::new(stream)),
+    };
+    let (mut parts, body) = response.into_parts();
+    parts.headers.insert(
+        axum::http::header::CONTENT_TYPE,
+        axum::http::HeaderValue::from_static(content_type),
+    );
+    Response::from_parts(parts, body)
+}
 
-    Response::new(encoder.finish().into())
+/// Whether the client's `Accept` header prefers the OpenMetrics exposition format over the
+/// classic Prometheus text format.
+fn wants_openmetrics(headers: &HeaderMap) -> bool {
+    headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/openmetrics-text"))
 }
 
 #[derive(LabelGroup)]